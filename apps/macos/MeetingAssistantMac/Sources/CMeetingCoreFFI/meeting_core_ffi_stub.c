@@ -56,4 +56,12 @@ void ma_free_c_string(char* ptr) {
   }
 }
 
+const char* ma_runtime_version(void) {
+  return "0.0.0-stub";
+}
+
+uint32_t ma_ffi_abi_version(void) {
+  return MEETING_CORE_FFI_ABI_VERSION;
+}
+
 #endif
@@ -88,6 +88,11 @@ public struct BootstrapState: Codable {
 public enum LocaleCode: String, Codable, CaseIterable, Identifiable {
     case zhCN = "zh-CN"
     case enUS = "en-US"
+    case jaJP = "ja-JP"
+    case koKR = "ko-KR"
+    case frFR = "fr-FR"
+    case deDE = "de-DE"
+    case esES = "es-ES"
 
     public var id: String { rawValue }
 }
@@ -198,6 +203,14 @@ public final class CoreClient {
         }
     }
 
+    public static var runtimeVersion: String {
+        String(cString: ma_runtime_version())
+    }
+
+    public static var ffiAbiVersion: UInt32 {
+        ma_ffi_abi_version()
+    }
+
     public func subscribe(_ handler: @escaping (String, String) -> Void) {
         eventHandler = handler
     }
@@ -334,16 +347,8 @@ public final class CoreClient {
         return String(cString: rawPtr)
     }
 
-    fileprivate func dispatchEventJSON(_ json: String) {
-        let data = json.data(using: .utf8) ?? Data()
-        if
-            let object = try? JSONSerialization.jsonObject(with: data) as? [String: Any],
-            let event = object["event"] as? String
-        {
-            eventHandler?(event, json)
-        } else {
-            eventHandler?("runtime://unknown", json)
-        }
+    fileprivate func dispatchEvent(name: String, json: String) {
+        eventHandler?(name.isEmpty ? "runtime://unknown" : name, json)
     }
 }
 
@@ -357,6 +362,7 @@ public struct UserPreferences: Codable {
 }
 
 private func coreBridgeEventCallback(
+    eventName: UnsafePointer<CChar>?,
     eventJSON: UnsafePointer<CChar>?,
     userData: UnsafeMutableRawPointer?
 ) -> Void {
@@ -367,8 +373,9 @@ private func coreBridgeEventCallback(
         return
     }
     let client = Unmanaged<CoreClient>.fromOpaque(userData).takeUnretainedValue()
+    let name = eventName.map { String(cString: $0) } ?? ""
     let json = String(cString: eventJSON)
     DispatchQueue.main.async {
-        client.dispatchEventJSON(json)
+        client.dispatchEvent(name: name, json: json)
     }
 }
@@ -1213,6 +1213,16 @@ struct MainWindowView: View {
             return "中文"
         case .enUS:
             return "English"
+        case .jaJP:
+            return "日本語"
+        case .koKR:
+            return "한국어"
+        case .frFR:
+            return "Français"
+        case .deDE:
+            return "Deutsch"
+        case .esES:
+            return "Español"
         }
     }
 
@@ -1,4 +1,13 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::Instant,
+};
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -6,6 +15,34 @@ use uuid::Uuid;
 
 type SharedCallback = Arc<dyn Fn(&str, &Value) + Send + Sync>;
 
+const PROFILE_BUNDLE_VERSION: u32 = 1;
+
+const STATE_FILE_NAME: &str = "meeting-assistant-state.json";
+
+// Bump when the persisted state layout changes; files written before the field existed
+// are version 1.
+const STATE_SCHEMA_VERSION: u32 = 1;
+
+const SUPPORTED_LOCALES: &[&str] = &[
+    "zh-CN", "en-US", "ja-JP", "ko-KR", "fr-FR", "de-DE", "es-ES",
+];
+
+const LIVE_OVERLAY_LABEL: &str = "live_overlay";
+const HINTS_OVERLAY_LABEL: &str = "hints_overlay";
+
+// Environment fallbacks for provider secrets, as (ProviderKind, ProviderSecretField, variable).
+// Aliyun is only configured when all three of its fields are present.
+const PROVIDER_SECRET_ENV_VARS: &[(&str, &str, &str)] = &[
+    ("aliyun", "access_key_id", "ALIYUN_ACCESS_KEY_ID"),
+    ("aliyun", "access_key_secret", "ALIYUN_ACCESS_KEY_SECRET"),
+    ("aliyun", "app_key", "ALIYUN_APP_KEY"),
+    ("deepgram", "api_key", "DEEPGRAM_API_KEY"),
+    ("claude", "api_key", "ANTHROPIC_API_KEY"),
+    ("gemini", "api_key", "GEMINI_API_KEY"),
+    ("openai", "api_key", "OPENAI_API_KEY"),
+    ("custom_llm", "api_key", "CUSTOM_LLM_API_KEY"),
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RuntimeConfig {
@@ -24,6 +61,8 @@ pub struct LiveOverlayLayout {
     pub width: u32,
     pub height: u32,
     pub anchor_screen: Option<String>,
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f64,
 }
 
 impl Default for LiveOverlayLayout {
@@ -35,10 +74,81 @@ impl Default for LiveOverlayLayout {
             width: 920,
             height: 480,
             anchor_screen: None,
+            font_scale: default_font_scale(),
+        }
+    }
+}
+
+impl LiveOverlayLayout {
+    fn clamp(&mut self) {
+        self.opacity = self.opacity.clamp(0.35, 1.0);
+        self.width = self.width.clamp(560, 1920);
+        self.height = self.height.clamp(260, 1080);
+        self.font_scale = self.font_scale.clamp(0.75, 2.5);
+    }
+
+    fn snap(&mut self, area: &OverlayRegion, edge: &str, threshold: i32) -> bool {
+        // Work out the bounds in i64: host-supplied work areas can push x + width past i32.
+        let left = i64::from(area.x);
+        let top = i64::from(area.y);
+        let max = i64::from(i32::MAX);
+        let right = (left + i64::from(area.width) - i64::from(self.width)).clamp(left, max);
+        let bottom = (top + i64::from(area.height) - i64::from(self.height)).clamp(top, max);
+        let threshold = i64::from(threshold);
+        let mut x = i64::from(self.x).clamp(left, right);
+        let mut y = i64::from(self.y).clamp(top, bottom);
+
+        let (snap_left, snap_right, snap_top, snap_bottom) = match edge {
+            "left" => (true, false, false, false),
+            "right" => (false, true, false, false),
+            "top" => (false, false, true, false),
+            "bottom" => (false, false, false, true),
+            "top_left" => (true, false, true, false),
+            "top_right" => (false, true, true, false),
+            "bottom_left" => (true, false, false, true),
+            "bottom_right" => (false, true, false, true),
+            "nearest" => (
+                x - left <= threshold,
+                right - x <= threshold,
+                y - top <= threshold,
+                bottom - y <= threshold,
+            ),
+            _ => return false,
+        };
+        if snap_left {
+            x = left;
+        } else if snap_right {
+            x = right;
         }
+        if snap_top {
+            y = top;
+        } else if snap_bottom {
+            y = bottom;
+        }
+        // Both were clamped between an i32 origin and i32::MAX above.
+        self.x = i32::try_from(x).unwrap_or(i32::MAX);
+        self.y = i32::try_from(y).unwrap_or(i32::MAX);
+        true
     }
 }
 
+fn default_font_scale() -> f64 {
+    1.0
+}
+
+fn default_snap_threshold() -> i32 {
+    24
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WindowModeState {
@@ -61,6 +171,15 @@ impl Default for WindowModeState {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayWindowState {
+    #[serde(default)]
+    pub layout: LiveOverlayLayout,
+    #[serde(default)]
+    pub mode: WindowModeState,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LlmSettings {
@@ -72,6 +191,14 @@ pub struct LlmSettings {
     pub base_url: Option<String>,
     #[serde(default = "default_api_format")]
     pub api_format: String,
+    #[serde(default = "default_system_prompt_mode")]
+    pub system_prompt_mode: String,
+    #[serde(default = "default_auth_style")]
+    pub auth_style: String,
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
 }
 
 impl Default for LlmSettings {
@@ -81,6 +208,10 @@ impl Default for LlmSettings {
             model: default_model(),
             base_url: None,
             api_format: default_api_format(),
+            system_prompt_mode: default_system_prompt_mode(),
+            auth_style: default_auth_style(),
+            stop_sequences: Vec::new(),
+            extra_headers: HashMap::new(),
         }
     }
 }
@@ -97,6 +228,14 @@ fn default_api_format() -> String {
     "anthropic".to_string()
 }
 
+fn default_system_prompt_mode() -> String {
+    "system".to_string()
+}
+
+fn default_auth_style() -> String {
+    "bearer".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserPreferences {
@@ -112,6 +251,20 @@ pub struct UserPreferences {
     pub teleprompter_mode: WindowModeState,
     #[serde(default)]
     pub live_overlay_layout: LiveOverlayLayout,
+    #[serde(default)]
+    pub monitor_overlay_layouts: HashMap<String, LiveOverlayLayout>,
+    #[serde(default)]
+    pub overlay_windows: HashMap<String, OverlayWindowState>,
+    #[serde(default = "default_overlay_content_mode")]
+    pub overlay_content_mode: String,
+    #[serde(default = "default_attachment_char_limit")]
+    pub attachment_char_limit: usize,
+    #[serde(default = "default_overlay_hotkey")]
+    pub overlay_hotkey: String,
+    #[serde(default = "default_show_interim")]
+    pub show_interim: bool,
+    #[serde(default = "default_translation_mode")]
+    pub translation_mode: String,
 }
 
 impl Default for UserPreferences {
@@ -123,26 +276,55 @@ impl Default for UserPreferences {
             llm_settings: LlmSettings::default(),
             teleprompter_mode: WindowModeState::default(),
             live_overlay_layout: LiveOverlayLayout::default(),
+            monitor_overlay_layouts: HashMap::new(),
+            overlay_windows: HashMap::new(),
+            overlay_content_mode: default_overlay_content_mode(),
+            attachment_char_limit: default_attachment_char_limit(),
+            overlay_hotkey: default_overlay_hotkey(),
+            show_interim: default_show_interim(),
+            translation_mode: default_translation_mode(),
         }
     }
 }
 
 fn default_locale() -> String {
-    if std::env::var("LANG")
-        .unwrap_or_default()
-        .to_lowercase()
-        .starts_with("zh")
-    {
-        "zh-CN".to_string()
-    } else {
-        "en-US".to_string()
-    }
+    let lang = std::env::var("LANG").unwrap_or_default().to_lowercase();
+    let locale = match lang.get(..2).unwrap_or_default() {
+        "zh" => "zh-CN",
+        "ja" => "ja-JP",
+        "ko" => "ko-KR",
+        "fr" => "fr-FR",
+        "de" => "de-DE",
+        "es" => "es-ES",
+        _ => "en-US",
+    };
+    locale.to_string()
 }
 
 fn default_theme_mode() -> String {
     "system".to_string()
 }
 
+fn default_overlay_content_mode() -> String {
+    "both".to_string()
+}
+
+fn default_attachment_char_limit() -> usize {
+    20_000
+}
+
+fn default_overlay_hotkey() -> String {
+    "CmdOrCtrl+Shift+M".to_string()
+}
+
+fn default_show_interim() -> bool {
+    true
+}
+
+fn default_translation_mode() -> String {
+    "provider_builtin".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct MeetingProfile {
@@ -153,6 +335,48 @@ pub struct MeetingProfile {
     pub language: String,
     pub self_intro: String,
     pub context_notes: String,
+    #[serde(default)]
+    pub hint_system_prompt: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<AttachmentRecord>,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentRecord {
+    pub id: String,
+    pub profile_id: String,
+    pub file_path: String,
+    pub file_type: String,
+    pub extracted_text: String,
+    #[serde(default)]
+    pub original_length: usize,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileBundle {
+    pub version: u32,
+    pub exported_at: String,
+    pub profiles: Vec<MeetingProfile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecord {
+    pub id: String,
+    pub profile_id: Option<String>,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub provider: String,
+    // None until a transcript pipeline counts final segments.
+    #[serde(default)]
+    pub segment_count: Option<u64>,
+    #[serde(default)]
+    pub interrupted: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -178,16 +402,68 @@ pub struct InvokeResponse {
     pub data: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<InvokeError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<InvokeError>,
+}
+
+struct LiveSession {
+    id: String,
+    provider: String,
+    paused: bool,
+    speaker_role: String,
+    output_device_id: Option<String>,
+    started_at: Instant,
+    event_seq: AtomicU64,
+}
+
+impl LiveSession {
+    /// Next sequence number for an event tagged with this session, so hosts can detect
+    /// dropped or reordered events and request a re-sync.
+    fn next_seq(&self) -> u64 {
+        self.event_seq.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn metrics(&self) -> Value {
+        json!({
+            "sessionId": self.id,
+            "elapsedSeconds": self.started_at.elapsed().as_secs(),
+            "speakerRole": self.speaker_role,
+            // No transcript or hint pipeline runs in this runtime, so nothing is measured.
+            "finalSegments": Value::Null,
+            "hintsGenerated": Value::Null,
+            "asrLatencyMs": Value::Null
+        })
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedState {
+    #[serde(default)]
+    preferences: UserPreferences,
+    #[serde(default)]
+    profiles: Vec<MeetingProfile>,
+    #[serde(default)]
+    sessions: Vec<SessionRecord>,
 }
 
 struct RuntimeState {
     preferences: UserPreferences,
     profiles: Vec<MeetingProfile>,
     live_overlay_visible: bool,
+    overlay_interactive_regions: HashMap<String, OverlayRegion>,
+    open_overlays: HashSet<String>,
+    live_session: Option<LiveSession>,
+    sessions: Vec<SessionRecord>,
+    // Set by state_for_update; cleared once the snapshot is handed to persist_state.
+    dirty: bool,
 }
 
 pub struct Runtime {
     config: RuntimeConfig,
+    state_path: Option<PathBuf>,
+    corrupt_state_backup: Option<PathBuf>,
+    persist_lock: Mutex<()>,
     state: Mutex<RuntimeState>,
     callback: Mutex<Option<SharedCallback>>,
 }
@@ -203,17 +479,48 @@ impl Runtime {
             serde_json::from_str(config_json)?
         };
 
+        let state_path = resolve_data_dir(&config).map(|dir| dir.join(STATE_FILE_NAME));
+        let (mut persisted, corrupt_state_backup) = match state_path.as_ref() {
+            Some(path) if path.exists() => load_state_file(path)?,
+            _ => (PersistedState::default(), None),
+        };
+        // A process that exited without stop_live_session leaves records open; no session
+        // survives a restart, so close them as interrupted.
+        let mut interrupted = false;
+        for record in persisted
+            .sessions
+            .iter_mut()
+            .filter(|record| record.ended_at.is_none())
+        {
+            record.ended_at = Some(chrono_like_now());
+            record.interrupted = true;
+            interrupted = true;
+        }
+        let state = RuntimeState {
+            preferences: persisted.preferences,
+            profiles: persisted.profiles,
+            live_overlay_visible: false,
+            overlay_interactive_regions: HashMap::new(),
+            open_overlays: HashSet::new(),
+            live_session: None,
+            sessions: persisted.sessions,
+            dirty: interrupted,
+        };
+
         Ok(Self {
             config,
-            state: Mutex::new(RuntimeState {
-                preferences: UserPreferences::default(),
-                profiles: Vec::new(),
-                live_overlay_visible: false,
-            }),
+            state_path,
+            corrupt_state_backup,
+            persist_lock: Mutex::new(()),
+            state: Mutex::new(state),
             callback: Mutex::new(None),
         })
     }
 
+    pub fn version() -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
     pub fn set_event_callback<F>(&self, callback: F)
     where
         F: Fn(&str, &Value) + Send + Sync + 'static,
@@ -238,15 +545,19 @@ impl Runtime {
         };
 
         let payload = match response {
+            // The change already applied in memory and its events went out, so a failed
+            // write is reported alongside the result and retried on the next invoke.
             Ok(data) => InvokeResponse {
                 ok: true,
                 data: Some(data),
                 error: None,
+                warning: self.persist_state().err(),
             },
             Err(error) => InvokeResponse {
                 ok: false,
                 data: None,
                 error: Some(error),
+                warning: None,
             },
         };
 
@@ -259,50 +570,104 @@ impl Runtime {
     fn dispatch(&self, request: InvokeRequest) -> Result<Value, InvokeError> {
         match request.command.as_str() {
             "get_bootstrap_state" => self.get_bootstrap_state(),
+            "get_runtime_info" => Ok(json!({
+                "version": Self::version(),
+                "platform": self.platform(),
+                "dataDir": self
+                    .state_path
+                    .as_ref()
+                    .and_then(|path| path.parent())
+                    .map(|dir| dir.to_string_lossy().into_owned()),
+                "corruptStateBackup": self
+                    .corrupt_state_backup
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().into_owned()),
+                "features": {
+                    "persistence": self.state_path.is_some(),
+                    "audioCapture": false,
+                    "asr": false,
+                    "llm": false
+                }
+            })),
             "get_user_preferences" => {
                 let state = self.state.lock().expect("runtime state mutex poisoned");
                 Ok(serde_json::to_value(&state.preferences).unwrap_or_else(|_| json!({})))
             }
             "save_user_preferences" => self.save_user_preferences(request.payload),
+            "complete_onboarding" => Ok(self.set_onboarding_completed(true)),
+            "reset_onboarding" => Ok(self.set_onboarding_completed(false)),
             "get_llm_settings" => {
                 let state = self.state.lock().expect("runtime state mutex poisoned");
                 Ok(serde_json::to_value(&state.preferences.llm_settings).unwrap_or_else(|_| json!({})))
             }
             "save_llm_settings" => self.save_llm_settings(request.payload),
+            "list_provider_models" => list_provider_models(request.payload),
             "list_meeting_profiles" => {
                 let state = self.state.lock().expect("runtime state mutex poisoned");
                 Ok(serde_json::to_value(&state.profiles).unwrap_or_else(|_| json!([])))
             }
             "save_meeting_profile" => self.save_meeting_profile(request.payload),
+            "search_profiles" => self.search_profiles(request.payload),
             "delete_meeting_profile" => self.delete_meeting_profile(request.payload),
-            "extract_attachment_text" => Ok(json!({
-                "id": Uuid::new_v4().to_string(),
-                "profileId": request.payload.get("profileId").and_then(Value::as_str).unwrap_or_default(),
-                "filePath": request.payload.get("filePath").and_then(Value::as_str).unwrap_or_default(),
-                "fileType": request.payload.get("fileType").and_then(Value::as_str).unwrap_or("txt"),
-                "extractedText": "",
-                "createdAt": chrono_like_now(),
-            })),
+            "export_profiles" => self.export_profiles(),
+            "import_profiles" => self.import_profiles(request.payload),
+            "extract_attachment_text" => self.extract_attachment_text(request.payload),
+            "delete_attachment" => self.delete_attachment(request.payload),
             "save_provider_secret" | "save_provider_key" => Ok(json!({ "saved": true })),
+            // Keys live in the host's secret store or the environment; the core cannot
+            // remove either, so it must not report the provider as unconfigured.
+            "delete_provider_secret" | "delete_provider_key" => Err(InvokeError {
+                code: "unsupported".to_string(),
+                message: "FFI runtime skeleton holds no provider secrets; delete the key from the host secret store".to_string(),
+            }),
+            "get_secret_field_status" => Ok(secret_field_status(&env_var)),
             "list_audio_devices" => Ok(json!({
                 "microphones": [],
+                "outputs": [],
                 "systemLoopbackAvailable": false,
                 "note": "FFI core skeleton has no direct audio enumeration yet"
             })),
-            "start_live_session" => self.start_live_session(),
+            "start_live_session" => self.start_live_session(request.payload),
             "stop_live_session" => self.stop_live_session(),
-            "show_live_overlay" => self.show_live_overlay(),
+            "pause_live_session" => self.set_live_session_paused(true),
+            "resume_live_session" => self.set_live_session_paused(false),
+            "switch_asr_provider" => self.switch_asr_provider(request.payload),
+            "set_active_speaker" => self.set_active_speaker(request.payload),
+            "request_hint" => self.request_hint(request.payload),
+            "test_hint" => self.test_hint(request.payload),
+            "get_session_metrics" => self.get_session_metrics(),
+            "session_status" => Ok(self.session_status()),
+            "list_sessions" => {
+                let state = self.state.lock().expect("runtime state mutex poisoned");
+                let sessions = state.sessions.iter().rev().collect::<Vec<_>>();
+                Ok(serde_json::to_value(sessions).unwrap_or_else(|_| json!([])))
+            }
+            "delete_session" => self.delete_session(request.payload),
+            "show_live_overlay" => self.show_live_overlay(request.payload),
             "hide_live_overlay" => self.hide_live_overlay(),
+            "open_overlay" => self.open_overlay(request.payload),
+            "close_overlay" => self.close_overlay(request.payload),
             "set_live_overlay_mode" | "set_teleprompter_mode" => {
                 self.set_live_overlay_mode(request.payload)
             }
             "get_live_overlay_layout" => {
+                let label = overlay_label(&request.payload)?;
                 let state = self.state.lock().expect("runtime state mutex poisoned");
-                Ok(serde_json::to_value(&state.preferences.live_overlay_layout)
+                Ok(serde_json::to_value(overlay_layout(&state, &label))
                     .unwrap_or_else(|_| json!({})))
             }
             "save_live_overlay_layout" => self.save_live_overlay_layout(request.payload),
+            "preview_live_overlay_layout" => self.preview_live_overlay_layout(request.payload),
+            "set_overlay_content_mode" => self.set_overlay_content_mode(request.payload),
+            "set_show_interim" => self.set_show_interim(request.payload),
+            "set_translation_mode" => self.set_translation_mode(request.payload),
+            "save_overlay_hotkey" => self.save_overlay_hotkey(request.payload),
+            "set_overlay_interactive_region" => {
+                self.set_overlay_interactive_region(request.payload)
+            }
             "start_live_overlay_drag" => Ok(json!({ "started": true })),
+            "snap_overlay" => self.snap_overlay(request.payload),
+            "check_overlay_transparency_support" => Ok(self.check_overlay_transparency_support()),
             _ => Err(InvokeError {
                 code: "unknown_command".to_string(),
                 message: format!("unsupported command: {}", request.command),
@@ -310,13 +675,60 @@ impl Runtime {
         }
     }
 
-    fn get_bootstrap_state(&self) -> Result<Value, InvokeError> {
-        let state = self.state.lock().expect("runtime state mutex poisoned");
-        let platform = self
-            .config
+    fn state_for_update(&self) -> MutexGuard<'_, RuntimeState> {
+        let mut state = self.state.lock().expect("runtime state mutex poisoned");
+        state.dirty = true;
+        state
+    }
+
+    fn persist_state(&self) -> Result<(), InvokeError> {
+        let Some(path) = self.state_path.as_ref() else {
+            return Ok(());
+        };
+        // Held from snapshot through rename so concurrent invokes reach disk in the order
+        // their snapshots were taken.
+        let _persist = self.persist_lock.lock().expect("persist mutex poisoned");
+        let snapshot = {
+            let mut state = self.state.lock().expect("runtime state mutex poisoned");
+            if !state.dirty {
+                return Ok(());
+            }
+            state.dirty = false;
+            state_snapshot(&state)
+        };
+
+        let storage_failure = |error: std::io::Error| InvokeError {
+            code: "storage_failure".to_string(),
+            message: format!("failed to write {}: {error}", path.display()),
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                let tmp_path = path.with_extension("json.tmp");
+                fs::write(&tmp_path, &snapshot)?;
+                fs::rename(&tmp_path, path)
+            });
+        if let Err(error) = written {
+            self.state
+                .lock()
+                .expect("runtime state mutex poisoned")
+                .dirty = true;
+            return Err(storage_failure(error));
+        }
+        Ok(())
+    }
+
+    fn platform(&self) -> String {
+        self.config
             .platform
             .clone()
-            .unwrap_or_else(|| std::env::consts::OS.to_string());
+            .unwrap_or_else(|| std::env::consts::OS.to_string())
+    }
+
+    fn get_bootstrap_state(&self) -> Result<Value, InvokeError> {
+        let state = self.state.lock().expect("runtime state mutex poisoned");
+        let platform = self.platform();
         let platform_style = match platform.as_str() {
             "macos" => "macos",
             "windows" => "windows",
@@ -324,21 +736,19 @@ impl Runtime {
         };
 
         Ok(json!({
-            "providerStatus": {
-                "aliyun": false,
-                "deepgram": false,
-                "claude": false,
-                "gemini": false,
-                "openai": false,
-                "customLlm": false
-            },
+            "providerStatus": provider_status(&env_var),
             "audioDevices": {
                 "microphones": [],
+                "outputs": [],
                 "systemLoopbackAvailable": false,
                 "note": "FFI runtime skeleton does not enumerate audio devices"
             },
             "teleprompter": state.preferences.teleprompter_mode,
             "liveOverlayLayout": state.preferences.live_overlay_layout,
+            "overlayContentMode": state.preferences.overlay_content_mode,
+            "overlayHotkey": state.preferences.overlay_hotkey,
+            "showInterim": state.preferences.show_interim,
+            "translationMode": state.preferences.translation_mode,
             "platform": platform,
             "platformStyle": platform_style,
             "locale": state.preferences.locale,
@@ -346,7 +756,8 @@ impl Runtime {
             "onboardingCompleted": state.preferences.onboarding_completed,
             "llmSettings": state.preferences.llm_settings,
             "windows": {
-                "liveOverlay": state.live_overlay_visible
+                "liveOverlay": state.live_overlay_visible,
+                "hintsOverlay": state.open_overlays.contains(HINTS_OVERLAY_LABEL)
             }
         }))
     }
@@ -358,19 +769,131 @@ impl Runtime {
             locale: String,
             theme_mode: String,
             onboarding_completed: bool,
+            #[serde(default)]
+            attachment_char_limit: Option<usize>,
         }
 
         let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
-        let mut state = self.state.lock().expect("runtime state mutex poisoned");
+        if !SUPPORTED_LOCALES.contains(&input.locale.as_str()) {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: format!("unsupported locale: {}", input.locale),
+            });
+        }
+        let mut state = self.state_for_update();
         state.preferences.locale = input.locale;
         state.preferences.theme_mode = input.theme_mode;
         state.preferences.onboarding_completed = input.onboarding_completed;
+        if let Some(limit) = input.attachment_char_limit {
+            state.preferences.attachment_char_limit = limit.max(1_000);
+        }
         Ok(serde_json::to_value(&state.preferences).unwrap_or_else(|_| json!({})))
     }
 
+    fn set_onboarding_completed(&self, completed: bool) -> Value {
+        let mut state = self.state_for_update();
+        state.preferences.onboarding_completed = completed;
+        let payload = json!({ "onboardingCompleted": completed });
+        drop(state);
+        self.emit_event("preferences://onboarding", &payload);
+        payload
+    }
+
     fn save_llm_settings(&self, payload: Value) -> Result<Value, InvokeError> {
-        let parsed = serde_json::from_value::<LlmSettings>(payload).map_err(invalid_payload)?;
-        let mut state = self.state.lock().expect("runtime state mutex poisoned");
+        // Hosts that predate these fields keep the stored values instead of resetting them.
+        let has_system_prompt_mode = payload.get("systemPromptMode").is_some();
+        let has_auth_style = payload.get("authStyle").is_some();
+        let has_stop_sequences = payload.get("stopSequences").is_some();
+        let has_extra_headers = payload.get("extraHeaders").is_some();
+        let mut parsed = serde_json::from_value::<LlmSettings>(payload).map_err(invalid_payload)?;
+        // "system" sends a top-level system prompt; "first_user_message" folds it into the
+        // first user turn for gateways that reject the system role.
+        if !matches!(
+            parsed.system_prompt_mode.as_str(),
+            "system" | "first_user_message"
+        ) {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: format!(
+                    "unsupported systemPromptMode: {}",
+                    parsed.system_prompt_mode
+                ),
+            });
+        }
+        // "bearer" sends Authorization: Bearer, "api_key_header" sends api-key (Azure OpenAI),
+        // and "query_param" appends api-key to the request URL.
+        if !matches!(
+            parsed.auth_style.as_str(),
+            "bearer" | "api_key_header" | "query_param"
+        ) {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: format!("unsupported authStyle: {}", parsed.auth_style),
+            });
+        }
+        parsed
+            .stop_sequences
+            .retain(|sequence| !sequence.is_empty());
+        let mut extra_headers = HashMap::with_capacity(parsed.extra_headers.len());
+        for (name, value) in parsed.extra_headers {
+            let name = name.trim().to_ascii_lowercase();
+            // Header names are RFC 7230 tokens; framing headers stay owned by the HTTP client.
+            let valid = !name.is_empty()
+                && name
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte));
+            let framing = matches!(
+                name.as_str(),
+                "host" | "content-length" | "content-type" | "transfer-encoding" | "connection"
+            );
+            if !valid || framing {
+                return Err(InvokeError {
+                    code: "invalid_payload".to_string(),
+                    message: format!("unsupported extra header name: {name}"),
+                });
+            }
+            // LLM settings are persisted in plaintext and echoed back to the UI, so
+            // credentials belong in the secret store instead.
+            if matches!(
+                name.as_str(),
+                "authorization" | "proxy-authorization" | "api-key" | "x-api-key"
+            ) {
+                return Err(InvokeError {
+                    code: "invalid_payload".to_string(),
+                    message: format!(
+                        "extra header {name} carries credentials; store the key with save_provider_secret"
+                    ),
+                });
+            }
+            // RFC 7230 field values allow visible characters, spaces and tabs only.
+            if value.chars().any(|c| c.is_control() && c != '\t') {
+                return Err(InvokeError {
+                    code: "invalid_payload".to_string(),
+                    message: format!("extra header {name} contains a control character"),
+                });
+            }
+            if extra_headers.insert(name.clone(), value).is_some() {
+                return Err(InvokeError {
+                    code: "invalid_payload".to_string(),
+                    message: format!("duplicate extra header name: {name}"),
+                });
+            }
+        }
+        parsed.extra_headers = extra_headers;
+        let mut state = self.state_for_update();
+        let stored = &state.preferences.llm_settings;
+        if !has_system_prompt_mode {
+            parsed.system_prompt_mode = stored.system_prompt_mode.clone();
+        }
+        if !has_auth_style {
+            parsed.auth_style = stored.auth_style.clone();
+        }
+        if !has_stop_sequences {
+            parsed.stop_sequences = stored.stop_sequences.clone();
+        }
+        if !has_extra_headers {
+            parsed.extra_headers = stored.extra_headers.clone();
+        }
         state.preferences.llm_settings = parsed.clone();
         Ok(serde_json::to_value(parsed).unwrap_or_else(|_| json!({})))
     }
@@ -386,10 +909,19 @@ impl Runtime {
             language: String,
             self_intro: String,
             context_notes: String,
+            #[serde(default)]
+            hint_system_prompt: Option<String>,
         }
 
+        // Hosts that predate hintSystemPrompt keep the stored prompt on update.
+        let has_hint_system_prompt = payload.get("hintSystemPrompt").is_some();
         let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
-        let mut state = self.state.lock().expect("runtime state mutex poisoned");
+        // An empty prompt means "use the default hint system prompt".
+        let hint_system_prompt = input
+            .hint_system_prompt
+            .map(|prompt| prompt.trim().to_string())
+            .filter(|prompt| !prompt.is_empty());
+        let mut state = self.state_for_update();
         let id = input.id.unwrap_or_else(|| Uuid::new_v4().to_string());
         if let Some(profile) = state.profiles.iter_mut().find(|profile| profile.id == id) {
             profile.name = input.name;
@@ -398,6 +930,10 @@ impl Runtime {
             profile.language = input.language;
             profile.self_intro = input.self_intro;
             profile.context_notes = input.context_notes;
+            if has_hint_system_prompt {
+                profile.hint_system_prompt = hint_system_prompt;
+            }
+            profile.updated_at = chrono_like_now();
             return Ok(serde_json::to_value(profile).unwrap_or_else(|_| json!({})));
         }
 
@@ -409,11 +945,47 @@ impl Runtime {
             language: input.language,
             self_intro: input.self_intro,
             context_notes: input.context_notes,
+            hint_system_prompt,
+            attachments: Vec::new(),
+            updated_at: chrono_like_now(),
         };
         state.profiles.push(profile.clone());
         Ok(serde_json::to_value(profile).unwrap_or_else(|_| json!({})))
     }
 
+    fn search_profiles(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            query: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let query = input.query.trim().to_lowercase();
+        let state = self.state.lock().expect("runtime state mutex poisoned");
+        // Rank by the first matching field: name, then domain, meeting type, notes.
+        let mut matches = state
+            .profiles
+            .iter()
+            .filter_map(|profile| {
+                [
+                    &profile.name,
+                    &profile.domain,
+                    &profile.meeting_type,
+                    &profile.context_notes,
+                ]
+                .iter()
+                .position(|field| field.to_lowercase().contains(&query))
+                .map(|rank| (rank, profile))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(rank, _)| *rank);
+        let profiles = matches
+            .into_iter()
+            .map(|(_, profile)| profile)
+            .collect::<Vec<_>>();
+        Ok(serde_json::to_value(profiles).unwrap_or_else(|_| json!([])))
+    }
+
     fn delete_meeting_profile(&self, payload: Value) -> Result<Value, InvokeError> {
         #[derive(Deserialize)]
         struct Input {
@@ -421,17 +993,188 @@ impl Runtime {
         }
 
         let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
-        let mut state = self.state.lock().expect("runtime state mutex poisoned");
+        let mut state = self.state_for_update();
         state.profiles.retain(|profile| profile.id != input.id);
         Ok(json!({ "deleted": true }))
     }
 
-    fn start_live_session(&self) -> Result<Value, InvokeError> {
+    fn export_profiles(&self) -> Result<Value, InvokeError> {
+        let state = self.state.lock().expect("runtime state mutex poisoned");
+        let bundle = ProfileBundle {
+            version: PROFILE_BUNDLE_VERSION,
+            exported_at: chrono_like_now(),
+            profiles: state.profiles.clone(),
+        };
+        serde_json::to_string(&bundle)
+            .map(Value::String)
+            .map_err(|error| InvokeError {
+                code: "serialization_failure".to_string(),
+                message: error.to_string(),
+            })
+    }
+
+    fn import_profiles(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            bundle: String,
+            #[serde(default)]
+            merge: bool,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let bundle =
+            serde_json::from_str::<ProfileBundle>(&input.bundle).map_err(invalid_payload)?;
+        if bundle.version != PROFILE_BUNDLE_VERSION {
+            return Err(InvokeError {
+                code: "unsupported_bundle_version".to_string(),
+                message: format!("unsupported profile bundle version: {}", bundle.version),
+            });
+        }
+
+        let imported = bundle.profiles.len();
+        let mut state = self.state_for_update();
+        if !input.merge {
+            state.profiles.clear();
+        }
+        // Checking against profiles pushed so far also catches ids repeated inside the bundle.
+        for mut profile in bundle.profiles {
+            if state
+                .profiles
+                .iter()
+                .any(|existing| existing.id == profile.id)
+            {
+                profile.id = Uuid::new_v4().to_string();
+                for attachment in &mut profile.attachments {
+                    attachment.profile_id = profile.id.clone();
+                }
+            }
+            state.profiles.push(profile);
+        }
+        Ok(json!({ "imported": imported, "total": state.profiles.len() }))
+    }
+
+    fn extract_attachment_text(&self, payload: Value) -> Result<Value, InvokeError> {
+        // The FFI runtime does not read attachment files yet, so the text is always empty and
+        // the attachmentCharLimit cap below only takes effect once extraction is wired in.
+        let extracted_text = String::new();
+        let profile_id = payload
+            .get("profileId")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let mut state = self.state_for_update();
+        let limit = state.preferences.attachment_char_limit;
+        let profile = state
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.id == profile_id)
+            .ok_or_else(|| profile_not_found(profile_id))?;
+        let record = AttachmentRecord {
+            id: Uuid::new_v4().to_string(),
+            profile_id: profile_id.to_string(),
+            file_path: payload
+                .get("filePath")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            file_type: payload
+                .get("fileType")
+                .and_then(Value::as_str)
+                .unwrap_or("txt")
+                .to_string(),
+            original_length: extracted_text.chars().count(),
+            extracted_text: truncate_extracted_text(&extracted_text, limit),
+            created_at: chrono_like_now(),
+        };
+        profile.attachments.push(record.clone());
+        profile.updated_at = chrono_like_now();
+        Ok(serde_json::to_value(record).unwrap_or_else(|_| json!({})))
+    }
+
+    fn delete_attachment(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Input {
+            profile_id: String,
+            attachment_id: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let mut state = self.state_for_update();
+        let profile = state
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.id == input.profile_id)
+            .ok_or_else(|| profile_not_found(&input.profile_id))?;
+        let before = profile.attachments.len();
+        profile
+            .attachments
+            .retain(|attachment| attachment.id != input.attachment_id);
+        if profile.attachments.len() == before {
+            return Err(InvokeError {
+                code: "attachment_not_found".to_string(),
+                message: format!("attachment not found: {}", input.attachment_id),
+            });
+        }
+        profile.updated_at = chrono_like_now();
+        Ok(json!({ "deleted": true }))
+    }
+
+    fn start_live_session(&self, payload: Value) -> Result<Value, InvokeError> {
+        let restart = payload
+            .get("restart")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
         let session_id = Uuid::new_v4().to_string();
+        let profile_id = payload
+            .get("profileId")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        // Loopback source selection; kept on the session until system audio capture lands.
+        let output_device_id = payload
+            .get("outputDeviceId")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        // The running check, the restart teardown and the insert share one guard so two
+        // concurrent starts cannot both pass the check.
+        let (previous, seq) = {
+            let mut state = self.state_for_update();
+            if state.live_session.is_some() && !restart {
+                return Err(InvokeError {
+                    code: "session_already_running".to_string(),
+                    message: "a live session is already running".to_string(),
+                });
+            }
+            let previous = take_live_session(&mut state);
+            state.sessions.push(SessionRecord {
+                id: session_id.clone(),
+                profile_id,
+                started_at: chrono_like_now(),
+                ended_at: None,
+                provider: "aliyun".to_string(),
+                segment_count: None,
+                interrupted: false,
+            });
+            let session = LiveSession {
+                id: session_id.clone(),
+                provider: "aliyun".to_string(),
+                paused: false,
+                speaker_role: "me".to_string(),
+                output_device_id: output_device_id.clone(),
+                started_at: Instant::now(),
+                event_seq: AtomicU64::new(1),
+            };
+            let seq = session.next_seq();
+            state.live_session = Some(session);
+            (previous, seq)
+        };
+        if previous.is_some() {
+            self.emit_session_stopped(previous);
+        }
         self.emit_event(
             "session://state",
             &json!({
                 "sessionId": session_id,
+                "seq": seq,
                 "state": "running",
                 "message": "Session started from FFI runtime skeleton",
                 "degradedMode": true,
@@ -442,84 +1185,901 @@ impl Runtime {
             "sessionId": session_id,
             "degradedMode": true,
             "message": "FFI runtime skeleton: no real ASR attached yet",
-            "provider": "aliyun"
+            "provider": "aliyun",
+            "outputDeviceId": output_device_id
         }))
     }
 
     fn stop_live_session(&self) -> Result<Value, InvokeError> {
+        let session = take_live_session(&mut self.state_for_update());
+        self.emit_session_stopped(session);
+        Ok(json!({ "stopped": true }))
+    }
+
+    fn emit_session_stopped(&self, session: Option<LiveSession>) {
+        if let Some(session) = session.as_ref() {
+            let mut metrics = session.metrics();
+            metrics["seq"] = json!(session.next_seq());
+            self.emit_event("session://metrics", &metrics);
+        }
+        let (session_id, provider, seq) = session
+            .map(|session| {
+                let seq = session.next_seq();
+                (session.id, session.provider, Some(seq))
+            })
+            .unwrap_or_else(|| ("ffi".to_string(), "aliyun".to_string(), None));
         self.emit_event(
             "session://state",
             &json!({
-                "sessionId": "ffi",
+                "sessionId": session_id,
+                "seq": seq,
                 "state": "stopped",
                 "message": "Session stopped",
                 "degradedMode": true,
-                "provider": "aliyun"
+                "provider": provider
             }),
         );
-        Ok(json!({ "stopped": true }))
     }
 
-    fn show_live_overlay(&self) -> Result<Value, InvokeError> {
-        let mut state = self.state.lock().expect("runtime state mutex poisoned");
-        state.live_overlay_visible = true;
-        Ok(json!({ "liveOverlay": true }))
-    }
+    fn delete_session(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            id: String,
+        }
 
-    fn hide_live_overlay(&self) -> Result<Value, InvokeError> {
-        let mut state = self.state.lock().expect("runtime state mutex poisoned");
-        state.live_overlay_visible = false;
-        Ok(json!({ "liveOverlay": false }))
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let mut state = self.state_for_update();
+        if state
+            .live_session
+            .as_ref()
+            .is_some_and(|session| session.id == input.id)
+        {
+            return Err(InvokeError {
+                code: "session_running".to_string(),
+                message: "stop the live session before deleting it".to_string(),
+            });
+        }
+        let before = state.sessions.len();
+        state.sessions.retain(|record| record.id != input.id);
+        if state.sessions.len() == before {
+            return Err(InvokeError {
+                code: "session_not_found".to_string(),
+                message: format!("session not found: {}", input.id),
+            });
+        }
+        Ok(json!({ "deleted": true }))
     }
 
-    fn set_live_overlay_mode(&self, payload: Value) -> Result<Value, InvokeError> {
-        let mut parsed = serde_json::from_value::<WindowModeState>(payload).map_err(invalid_payload)?;
-        parsed.opacity = parsed.opacity.clamp(0.35, 1.0);
-        let mut state = self.state.lock().expect("runtime state mutex poisoned");
-        state.preferences.teleprompter_mode = parsed.clone();
-        state.preferences.live_overlay_layout.opacity = parsed.opacity;
+    fn set_live_session_paused(&self, paused: bool) -> Result<Value, InvokeError> {
+        let (session_id, provider, seq) = {
+            let mut state = self.state.lock().expect("runtime state mutex poisoned");
+            let Some(session) = state.live_session.as_mut() else {
+                return Err(session_not_running());
+            };
+            session.paused = paused;
+            (
+                session.id.clone(),
+                session.provider.clone(),
+                session.next_seq(),
+            )
+        };
+        let (lifecycle, message) = if paused {
+            ("paused", "Session paused; audio is dropped until resumed")
+        } else {
+            ("running", "Session resumed")
+        };
         self.emit_event(
-            "overlay://mode",
-            &serde_json::to_value(&parsed).unwrap_or_else(|_| json!({})),
+            "session://state",
+            &json!({
+                "sessionId": session_id,
+                "seq": seq,
+                "state": lifecycle,
+                "message": message,
+                "degradedMode": true,
+                "provider": provider
+            }),
         );
-        Ok(serde_json::to_value(parsed).unwrap_or_else(|_| json!({})))
+        Ok(json!({ "sessionId": session_id, "paused": paused }))
     }
 
-    fn save_live_overlay_layout(&self, payload: Value) -> Result<Value, InvokeError> {
-        let mut parsed =
-            serde_json::from_value::<LiveOverlayLayout>(payload).map_err(invalid_payload)?;
-        parsed.opacity = parsed.opacity.clamp(0.35, 1.0);
-        parsed.width = parsed.width.clamp(560, 1920);
-        parsed.height = parsed.height.clamp(260, 1080);
-        let mut state = self.state.lock().expect("runtime state mutex poisoned");
-        state.preferences.live_overlay_layout = parsed.clone();
-        state.preferences.teleprompter_mode.opacity = parsed.opacity;
-        self.emit_event(
-            "overlay://layout",
-            &serde_json::to_value(&parsed).unwrap_or_else(|_| json!({})),
-        );
-        Ok(serde_json::to_value(parsed).unwrap_or_else(|_| json!({})))
+    fn session_status(&self) -> Value {
+        let state = self.state.lock().expect("runtime state mutex poisoned");
+        match state.live_session.as_ref() {
+            Some(session) => json!({
+                "running": true,
+                "sessionId": session.id,
+                "provider": session.provider,
+                "state": if session.paused { "paused" } else { "running" },
+                "outputDeviceId": session.output_device_id,
+                "speakerRole": session.speaker_role,
+                "lastSeq": session.event_seq.load(Ordering::Relaxed) - 1,
+                "uptimeMs": session.started_at.elapsed().as_millis() as u64
+            }),
+            None => json!({
+                "running": false,
+                "sessionId": Value::Null,
+                "provider": Value::Null,
+                "state": "stopped",
+                "outputDeviceId": Value::Null,
+                "speakerRole": Value::Null,
+                "lastSeq": Value::Null,
+                "uptimeMs": 0
+            }),
+        }
     }
 
-    fn emit_event(&self, event: &str, payload: &Value) {
-        let callback = {
-            let guard = self.callback.lock().expect("callback mutex poisoned");
-            guard.clone()
+    fn get_session_metrics(&self) -> Result<Value, InvokeError> {
+        let metrics = {
+            let state = self.state.lock().expect("runtime state mutex poisoned");
+            let session = state
+                .live_session
+                .as_ref()
+                .ok_or_else(session_not_running)?;
+            let mut metrics = session.metrics();
+            metrics["seq"] = json!(session.next_seq());
+            metrics
         };
-        if let Some(callback) = callback {
-            let event_payload = json!({
-                "event": event,
-                "payload": payload
-            });
-            callback(event, &event_payload);
-        }
+        self.emit_event("session://metrics", &metrics);
+        Ok(metrics)
     }
-}
 
-fn invalid_payload(error: serde_json::Error) -> InvokeError {
-    InvokeError {
-        code: "invalid_payload".to_string(),
-        message: error.to_string(),
+    fn switch_asr_provider(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            provider: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        if !matches!(input.provider.as_str(), "aliyun" | "deepgram") {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: format!("unsupported ASR provider: {}", input.provider),
+            });
+        }
+
+        let (session_id, previous, seq) = {
+            let mut state = self.state_for_update();
+            let Some(session) = state.live_session.as_mut() else {
+                return Err(session_not_running());
+            };
+            let previous = std::mem::replace(&mut session.provider, input.provider.clone());
+            let session_id = session.id.clone();
+            let seq = session.next_seq();
+            if let Some(record) = state
+                .sessions
+                .iter_mut()
+                .find(|record| record.id == session_id)
+            {
+                record.provider = input.provider.clone();
+            }
+            (session_id, previous, seq)
+        };
+        self.emit_event(
+            "session://state",
+            &json!({
+                "sessionId": session_id,
+                "seq": seq,
+                "state": "running",
+                "message": format!("ASR provider switched from {previous} to {}", input.provider),
+                "degradedMode": true,
+                "provider": input.provider
+            }),
+        );
+        Ok(json!({
+            "sessionId": session_id,
+            "provider": input.provider,
+            "previousProvider": previous
+        }))
+    }
+
+    fn set_active_speaker(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            role: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        if !matches!(input.role.as_str(), "me" | "interviewer" | "other") {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: format!("unsupported speaker role: {}", input.role),
+            });
+        }
+        let (session_id, seq) = {
+            let mut state = self.state.lock().expect("runtime state mutex poisoned");
+            let Some(session) = state.live_session.as_mut() else {
+                return Err(session_not_running());
+            };
+            session.speaker_role = input.role.clone();
+            (session.id.clone(), session.next_seq())
+        };
+        let payload = json!({ "sessionId": session_id, "seq": seq, "role": input.role });
+        self.emit_event("session://speaker", &payload);
+        Ok(payload)
+    }
+
+    fn request_hint(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Input {
+            session_id: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let state = self.state.lock().expect("runtime state mutex poisoned");
+        match state.live_session.as_ref() {
+            Some(session) if session.id == input.session_id && session.paused => Err(InvokeError {
+                code: "session_paused".to_string(),
+                message: "hints are suppressed while the session is paused".to_string(),
+            }),
+            // Same as test_hint: without an LLM no hint runs, so nothing is counted or emitted.
+            Some(session) if session.id == input.session_id => Err(InvokeError {
+                code: "llm_unavailable".to_string(),
+                message: "FFI runtime skeleton: no LLM attached yet".to_string(),
+            }),
+            _ => Err(InvokeError {
+                code: "session_not_running".to_string(),
+                message: format!("no live session running with id {}", input.session_id),
+            }),
+        }
+    }
+
+    fn test_hint(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Input {
+            profile_id: String,
+            question: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        if input.question.trim().is_empty() {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: "question must not be empty".to_string(),
+            });
+        }
+        let state = self.state.lock().expect("runtime state mutex poisoned");
+        if !state
+            .profiles
+            .iter()
+            .any(|profile| profile.id == input.profile_id)
+        {
+            return Err(profile_not_found(&input.profile_id));
+        }
+        Err(InvokeError {
+            code: "llm_unavailable".to_string(),
+            message: "FFI runtime skeleton: no LLM attached yet".to_string(),
+        })
+    }
+
+    fn show_live_overlay(&self, payload: Value) -> Result<Value, InvokeError> {
+        let monitor = payload
+            .get("monitor")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let mut state = self.state_for_update();
+        state.live_overlay_visible = true;
+        let Some(monitor) = monitor else {
+            return Ok(json!({ "liveOverlay": true }));
+        };
+
+        let layout = state
+            .preferences
+            .monitor_overlay_layouts
+            .get(&monitor)
+            .cloned()
+            .unwrap_or_else(|| LiveOverlayLayout {
+                anchor_screen: Some(monitor),
+                ..LiveOverlayLayout::default()
+            });
+        state.preferences.live_overlay_layout = layout.clone();
+        state.preferences.teleprompter_mode.opacity = layout.opacity;
+        drop(state);
+        self.emit_overlay_event("overlay://layout", LIVE_OVERLAY_LABEL, &layout);
+        Ok(json!({ "liveOverlay": true, "layout": layout }))
+    }
+
+    fn hide_live_overlay(&self) -> Result<Value, InvokeError> {
+        let mut state = self.state.lock().expect("runtime state mutex poisoned");
+        state.live_overlay_visible = false;
+        Ok(json!({ "liveOverlay": false }))
+    }
+
+    fn open_overlay(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            kind: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let label = match input.kind.as_str() {
+            "transcript" => LIVE_OVERLAY_LABEL,
+            "hints" => HINTS_OVERLAY_LABEL,
+            _ => {
+                return Err(InvokeError {
+                    code: "invalid_payload".to_string(),
+                    message: format!("unsupported overlay kind: {}", input.kind),
+                })
+            }
+        };
+
+        let mut state = self.state_for_update();
+        let window = if label == LIVE_OVERLAY_LABEL {
+            state.live_overlay_visible = true;
+            OverlayWindowState {
+                layout: state.preferences.live_overlay_layout.clone(),
+                mode: state.preferences.teleprompter_mode.clone(),
+            }
+        } else {
+            state.open_overlays.insert(label.to_string());
+            state
+                .preferences
+                .overlay_windows
+                .entry(label.to_string())
+                .or_default()
+                .clone()
+        };
+        Ok(json!({
+            "label": label,
+            "visible": true,
+            "layout": window.layout,
+            "mode": window.mode
+        }))
+    }
+
+    fn close_overlay(&self, payload: Value) -> Result<Value, InvokeError> {
+        let label = overlay_label(&payload)?;
+        let mut state = self.state.lock().expect("runtime state mutex poisoned");
+        if label == LIVE_OVERLAY_LABEL {
+            state.live_overlay_visible = false;
+        } else {
+            state.open_overlays.remove(&label);
+        }
+        Ok(json!({ "label": label, "visible": false }))
+    }
+
+    fn set_live_overlay_mode(&self, payload: Value) -> Result<Value, InvokeError> {
+        let label = overlay_label(&payload)?;
+        let mut parsed = serde_json::from_value::<WindowModeState>(payload).map_err(invalid_payload)?;
+        parsed.opacity = parsed.opacity.clamp(0.35, 1.0);
+        let mut state = self.state_for_update();
+        let previous = if label == LIVE_OVERLAY_LABEL {
+            let previous =
+                std::mem::replace(&mut state.preferences.teleprompter_mode, parsed.clone());
+            state.preferences.live_overlay_layout.opacity = parsed.opacity;
+            previous
+        } else {
+            let window = state
+                .preferences
+                .overlay_windows
+                .entry(label.clone())
+                .or_default();
+            window.layout.opacity = parsed.opacity;
+            std::mem::replace(&mut window.mode, parsed.clone())
+        };
+        // Always-on-top, click-through and opacity apply live on every platform. Transparency
+        // is fixed when the overlay window is created, and decorations are handled the same
+        // way, so either change means the host recreates the window and re-applies the layout.
+        let requires_recreate = previous.transparent != parsed.transparent
+            || previous.undecorated != parsed.undecorated;
+        let mut mode = serde_json::to_value(&parsed).unwrap_or_else(|_| json!({}));
+        if let Some(fields) = mode.as_object_mut() {
+            fields.insert(
+                "requiresRecreate".to_string(),
+                Value::Bool(requires_recreate),
+            );
+        }
+        drop(state);
+        self.emit_overlay_event("overlay://mode", &label, &mode);
+        Ok(mode)
+    }
+
+    fn save_live_overlay_layout(&self, payload: Value) -> Result<Value, InvokeError> {
+        let label = overlay_label(&payload)?;
+        // Hosts that predate fontScale keep the stored scale instead of resetting it.
+        let has_font_scale = payload.get("fontScale").is_some();
+        let mut parsed =
+            serde_json::from_value::<LiveOverlayLayout>(payload).map_err(invalid_payload)?;
+        let mut state = self.state_for_update();
+        if label == LIVE_OVERLAY_LABEL {
+            if !has_font_scale {
+                parsed.font_scale = state.preferences.live_overlay_layout.font_scale;
+            }
+            parsed.clamp();
+            if let Some(monitor) = parsed.anchor_screen.clone() {
+                state
+                    .preferences
+                    .monitor_overlay_layouts
+                    .insert(monitor, parsed.clone());
+            }
+            state.preferences.live_overlay_layout = parsed.clone();
+            state.preferences.teleprompter_mode.opacity = parsed.opacity;
+        } else {
+            let window = state
+                .preferences
+                .overlay_windows
+                .entry(label.clone())
+                .or_default();
+            if !has_font_scale {
+                parsed.font_scale = window.layout.font_scale;
+            }
+            parsed.clamp();
+            window.layout = parsed.clone();
+            window.mode.opacity = parsed.opacity;
+        }
+        drop(state);
+        self.emit_overlay_event("overlay://layout", &label, &parsed);
+        Ok(serde_json::to_value(parsed).unwrap_or_else(|_| json!({})))
+    }
+
+    fn preview_live_overlay_layout(&self, payload: Value) -> Result<Value, InvokeError> {
+        let label = overlay_label(&payload)?;
+        let has_font_scale = payload.get("fontScale").is_some();
+        let mut parsed =
+            serde_json::from_value::<LiveOverlayLayout>(payload).map_err(invalid_payload)?;
+        if !has_font_scale {
+            let state = self.state.lock().expect("runtime state mutex poisoned");
+            parsed.font_scale = overlay_layout(&state, &label).font_scale;
+        }
+        parsed.clamp();
+        // Preview only: the stored layout is left untouched until save_live_overlay_layout,
+        // and the event is flagged so hosts do not treat it as committed state.
+        let mut preview = serde_json::to_value(&parsed).unwrap_or_else(|_| json!({}));
+        if let Some(fields) = preview.as_object_mut() {
+            fields.insert("preview".to_string(), Value::Bool(true));
+        }
+        self.emit_overlay_event("overlay://layout", &label, &preview);
+        Ok(serde_json::to_value(parsed).unwrap_or_else(|_| json!({})))
+    }
+
+    fn snap_overlay(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Input {
+            edge: String,
+            work_area: OverlayRegion,
+            #[serde(default = "default_snap_threshold")]
+            threshold: i32,
+        }
+
+        let label = overlay_label(&payload)?;
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let mut state = self.state_for_update();
+        let mut layout = overlay_layout(&state, &label);
+        if !layout.snap(&input.work_area, &input.edge, input.threshold) {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: format!("unsupported snap edge: {}", input.edge),
+            });
+        }
+        if label == LIVE_OVERLAY_LABEL {
+            if let Some(monitor) = layout.anchor_screen.clone() {
+                state
+                    .preferences
+                    .monitor_overlay_layouts
+                    .insert(monitor, layout.clone());
+            }
+            state.preferences.live_overlay_layout = layout.clone();
+        } else {
+            state
+                .preferences
+                .overlay_windows
+                .entry(label.clone())
+                .or_default()
+                .layout = layout.clone();
+        }
+        drop(state);
+        self.emit_overlay_event("overlay://layout", &label, &layout);
+        Ok(serde_json::to_value(layout).unwrap_or_else(|_| json!({})))
+    }
+
+    fn set_overlay_content_mode(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            mode: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        if !matches!(
+            input.mode.as_str(),
+            "transcript_only" | "translation_only" | "both" | "hints_only"
+        ) {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: format!("unsupported overlay content mode: {}", input.mode),
+            });
+        }
+        let mut state = self.state_for_update();
+        state.preferences.overlay_content_mode = input.mode.clone();
+        let payload = json!({ "mode": input.mode });
+        drop(state);
+        self.emit_event("overlay://content-mode", &payload);
+        Ok(payload)
+    }
+
+    fn set_overlay_interactive_region(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            #[serde(default)]
+            region: Option<OverlayRegion>,
+        }
+
+        let label = overlay_label(&payload)?;
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let mut state = self.state.lock().expect("runtime state mutex poisoned");
+        match input.region {
+            Some(region) => state
+                .overlay_interactive_regions
+                .insert(label.clone(), region),
+            None => state.overlay_interactive_regions.remove(&label),
+        };
+        let click_through = if label == LIVE_OVERLAY_LABEL {
+            state.preferences.teleprompter_mode.click_through
+        } else {
+            state
+                .preferences
+                .overlay_windows
+                .get(&label)
+                .is_some_and(|window| window.mode.click_through)
+        };
+        // With click-through on, the host passes clicks through everywhere except this
+        // region so the drag handle stays usable.
+        let payload = json!({
+            "label": label,
+            "clickThrough": click_through,
+            "region": input.region
+        });
+        drop(state);
+        self.emit_event("overlay://interactive-region", &payload);
+        Ok(payload)
+    }
+
+    fn set_show_interim(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Input {
+            show_interim: bool,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let mut state = self.state_for_update();
+        state.preferences.show_interim = input.show_interim;
+        let payload = json!({ "showInterim": input.show_interim });
+        drop(state);
+        self.emit_event("transcript://show-interim", &payload);
+        Ok(payload)
+    }
+
+    fn set_translation_mode(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            mode: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        if !matches!(input.mode.as_str(), "provider_builtin" | "llm" | "off") {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: format!("unsupported translation mode: {}", input.mode),
+            });
+        }
+        let mut state = self.state_for_update();
+        state.preferences.translation_mode = input.mode.clone();
+        let payload = json!({ "mode": input.mode });
+        drop(state);
+        self.emit_event("translation://mode", &payload);
+        Ok(payload)
+    }
+
+    fn save_overlay_hotkey(&self, payload: Value) -> Result<Value, InvokeError> {
+        #[derive(Deserialize)]
+        struct Input {
+            hotkey: String,
+        }
+
+        let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+        let hotkey = input.hotkey.trim().to_string();
+        validate_hotkey(&hotkey)?;
+        let mut state = self.state_for_update();
+        state.preferences.overlay_hotkey = hotkey.clone();
+        let payload = json!({ "hotkey": hotkey });
+        drop(state);
+        self.emit_event("overlay://hotkey", &payload);
+        Ok(payload)
+    }
+
+    fn check_overlay_transparency_support(&self) -> Value {
+        let platform = self.platform();
+        let (supported, reason) = match platform.as_str() {
+            "macos" => (true, "macOS always composites windows"),
+            "windows" => (true, "DWM composition is always enabled"),
+            _ => linux_transparency_support(),
+        };
+        json!({
+            "supported": supported,
+            "reason": reason,
+            "platform": platform
+        })
+    }
+
+    fn emit_overlay_event<T: Serialize>(&self, event: &str, label: &str, value: &T) {
+        let mut payload = serde_json::to_value(value).unwrap_or_else(|_| json!({}));
+        if let Some(fields) = payload.as_object_mut() {
+            fields.insert("label".to_string(), Value::String(label.to_string()));
+        }
+        self.emit_event(event, &payload);
+    }
+
+    fn emit_event(&self, event: &str, payload: &Value) {
+        let callback = {
+            let guard = self.callback.lock().expect("callback mutex poisoned");
+            guard.clone()
+        };
+        if let Some(callback) = callback {
+            let event_payload = json!({
+                "event": event,
+                "payload": payload
+            });
+            callback(event, &event_payload);
+        }
+    }
+}
+
+// Takes the live session out of the state and closes its history record.
+fn take_live_session(state: &mut RuntimeState) -> Option<LiveSession> {
+    let session = state.live_session.take()?;
+    if let Some(record) = state
+        .sessions
+        .iter_mut()
+        .find(|record| record.id == session.id)
+    {
+        record.ended_at = Some(chrono_like_now());
+    }
+    Some(session)
+}
+
+fn resolve_data_dir(config: &RuntimeConfig) -> Option<PathBuf> {
+    config
+        .data_dir
+        .clone()
+        .or_else(|| std::env::var("MEETING_ASSISTANT_DATA_DIR").ok())
+        .map(|dir| dir.trim().to_string())
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+// A state file that does not parse is moved aside rather than failing startup, so the host
+// can still run on defaults and the original is kept for recovery. I/O errors are returned:
+// they say nothing about the file's contents.
+fn load_state_file(path: &Path) -> anyhow::Result<(PersistedState, Option<PathBuf>)> {
+    let text = fs::read_to_string(path)?;
+    match serde_json::from_str(&text) {
+        Ok(persisted) => Ok((persisted, None)),
+        Err(_) => {
+            // Unique per corruption so an earlier backup is never overwritten.
+            let backup = path.with_extension(format!(
+                "json.corrupt-{}-{}",
+                chrono_like_now(),
+                &Uuid::new_v4().simple().to_string()[..8]
+            ));
+            fs::rename(path, &backup)?;
+            Ok((PersistedState::default(), Some(backup)))
+        }
+    }
+}
+
+fn state_snapshot(state: &RuntimeState) -> String {
+    serde_json::to_string_pretty(&json!({
+        "schemaVersion": STATE_SCHEMA_VERSION,
+        "preferences": state.preferences,
+        "profiles": state.profiles,
+        "sessions": state.sessions
+    }))
+    .unwrap_or_default()
+}
+
+fn overlay_label(payload: &Value) -> Result<String, InvokeError> {
+    let label = payload
+        .get("label")
+        .and_then(Value::as_str)
+        .unwrap_or(LIVE_OVERLAY_LABEL);
+    if label != LIVE_OVERLAY_LABEL && label != HINTS_OVERLAY_LABEL {
+        return Err(InvokeError {
+            code: "invalid_payload".to_string(),
+            message: format!("unsupported overlay label: {label}"),
+        });
+    }
+    Ok(label.to_string())
+}
+
+fn overlay_layout(state: &RuntimeState, label: &str) -> LiveOverlayLayout {
+    if label == LIVE_OVERLAY_LABEL {
+        state.preferences.live_overlay_layout.clone()
+    } else {
+        state
+            .preferences
+            .overlay_windows
+            .get(label)
+            .map(|window| window.layout.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn invalid_payload(error: serde_json::Error) -> InvokeError {
+    InvokeError {
+        code: "invalid_payload".to_string(),
+        message: error.to_string(),
+    }
+}
+
+fn validate_hotkey(hotkey: &str) -> Result<(), InvokeError> {
+    const MODIFIERS: &[&str] = &[
+        "cmdorctrl",
+        "commandorcontrol",
+        "cmd",
+        "command",
+        "ctrl",
+        "control",
+        "alt",
+        "option",
+        "shift",
+        "super",
+        "meta",
+    ];
+
+    let parts = hotkey.split('+').map(str::trim).collect::<Vec<_>>();
+    let invalid = |reason: &str| InvokeError {
+        code: "invalid_hotkey".to_string(),
+        message: format!("invalid hotkey {hotkey:?}: {reason}"),
+    };
+    let Some((key, modifiers)) = parts.split_last() else {
+        return Err(invalid("empty combination"));
+    };
+    if key.is_empty() || MODIFIERS.contains(&key.to_lowercase().as_str()) {
+        return Err(invalid("missing a non-modifier key"));
+    }
+    if modifiers.is_empty() {
+        return Err(invalid("at least one modifier is required"));
+    }
+    if let Some(unknown) = modifiers
+        .iter()
+        .find(|modifier| !MODIFIERS.contains(&modifier.to_lowercase().as_str()))
+    {
+        return Err(invalid(&format!("unknown modifier {unknown}")));
+    }
+    Ok(())
+}
+
+fn linux_transparency_support() -> (bool, &'static str) {
+    let env = |key: &str| std::env::var(key).unwrap_or_default();
+    if env("XDG_SESSION_TYPE").eq_ignore_ascii_case("wayland") || !env("WAYLAND_DISPLAY").is_empty()
+    {
+        return (true, "Wayland compositors support transparent windows");
+    }
+    if env("DISPLAY").is_empty() {
+        return (false, "no graphical session detected");
+    }
+
+    let desktop = env("XDG_CURRENT_DESKTOP").to_lowercase();
+    let compositing = [
+        "gnome", "kde", "cinnamon", "budgie", "pantheon", "deepin", "unity",
+    ];
+    if desktop.split(':').any(|name| compositing.contains(&name)) {
+        (
+            true,
+            "desktop environment runs a compositing window manager",
+        )
+    } else {
+        (
+            false,
+            "X11 session without a known compositing window manager",
+        )
+    }
+}
+
+fn list_provider_models(payload: Value) -> Result<Value, InvokeError> {
+    #[derive(Deserialize)]
+    struct Input {
+        provider: String,
+    }
+
+    let input = serde_json::from_value::<Input>(payload).map_err(invalid_payload)?;
+    // The core has no HTTP client, so only the curated fallback list is available here.
+    let models: &[&str] = match input.provider.as_str() {
+        "anthropic" => &[
+            "claude-3-5-sonnet-latest",
+            "claude-3-5-haiku-latest",
+            "claude-3-opus-latest",
+        ],
+        "openai" => &["gpt-4o", "gpt-4o-mini", "gpt-4.1", "gpt-4.1-mini"],
+        "gemini" => &["gemini-1.5-flash", "gemini-1.5-pro", "gemini-2.0-flash"],
+        "custom" => &[],
+        _ => {
+            return Err(InvokeError {
+                code: "invalid_payload".to_string(),
+                message: format!("unsupported LLM provider: {}", input.provider),
+            })
+        }
+    };
+    Ok(json!({
+        "provider": input.provider,
+        "models": models,
+        "source": "curated"
+    }))
+}
+
+// Secrets are looked up through `lookup` so tests can supply values without touching the
+// process environment; the runtime passes `env_var`.
+type SecretLookup<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+fn provider_configured(provider: &str, lookup: SecretLookup) -> bool {
+    PROVIDER_SECRET_ENV_VARS
+        .iter()
+        .filter(|(kind, _, _)| *kind == provider)
+        .all(|(_, _, var)| secret_present(var, lookup))
+}
+
+fn provider_status(lookup: SecretLookup) -> Value {
+    json!({
+        "aliyun": provider_configured("aliyun", lookup),
+        "deepgram": provider_configured("deepgram", lookup),
+        "claude": provider_configured("claude", lookup),
+        "gemini": provider_configured("gemini", lookup),
+        "openai": provider_configured("openai", lookup),
+        "customLlm": provider_configured("custom_llm", lookup)
+    })
+}
+
+fn secret_field_status(lookup: SecretLookup) -> Value {
+    let mut status = serde_json::Map::new();
+    for (provider, field, var) in PROVIDER_SECRET_ENV_VARS {
+        let fields = status
+            .entry(provider.to_string())
+            .or_insert_with(|| json!({}));
+        fields[*field] = Value::Bool(secret_present(var, lookup));
+    }
+    Value::Object(status)
+}
+
+fn env_var(var: &str) -> Option<String> {
+    std::env::var(var).ok()
+}
+
+fn secret_present(var: &str, lookup: SecretLookup) -> bool {
+    lookup(var).is_some_and(|value| !value.trim().is_empty())
+}
+
+fn truncate_extracted_text(text: &str, limit: usize) -> String {
+    let Some((cut, next)) = text.char_indices().nth(limit) else {
+        return text.to_string();
+    };
+    let head = &text[..cut];
+    // Prefer ending on a word boundary so the LLM does not see half a token. A cut that
+    // already lands on whitespace keeps the whole head, and a head with no earlier word
+    // boundary falls back to the hard cut.
+    let head = if next.is_whitespace() {
+        head
+    } else {
+        head.rfind(char::is_whitespace)
+            .map(|index| &head[..index])
+            .filter(|trimmed| !trimmed.trim().is_empty())
+            .unwrap_or(head)
+    };
+    format!("{}\n[truncated]", head.trim_end())
+}
+
+fn profile_not_found(id: &str) -> InvokeError {
+    InvokeError {
+        code: "profile_not_found".to_string(),
+        message: format!("meeting profile not found: {id}"),
+    }
+}
+
+fn session_not_running() -> InvokeError {
+    InvokeError {
+        code: "session_not_running".to_string(),
+        message: "no live session is running".to_string(),
     }
 }
 
@@ -532,3 +2092,526 @@ fn chrono_like_now() -> String {
     format!("{now}")
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDataDir(PathBuf);
+
+    impl Drop for TempDataDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn runtime() -> (Runtime, TempDataDir) {
+        let data_dir = std::env::temp_dir().join(format!("meeting-core-test-{}", Uuid::new_v4()));
+        let config = json!({ "dataDir": data_dir, "platform": "linux" });
+        let runtime = Runtime::new(&config.to_string()).expect("runtime starts");
+        (runtime, TempDataDir(data_dir))
+    }
+
+    fn invoke(runtime: &Runtime, command: &str, payload: Value) -> Value {
+        let request = json!({ "command": command, "payload": payload });
+        serde_json::from_str(&runtime.invoke_json(&request.to_string())).expect("response is JSON")
+    }
+
+    #[test]
+    fn aliyun_needs_every_secret_field() {
+        let (runtime, _data_dir) = runtime();
+        let mut secrets = HashMap::from([
+            ("ALIYUN_ACCESS_KEY_ID", "id"),
+            ("ALIYUN_ACCESS_KEY_SECRET", "secret"),
+            ("ALIYUN_APP_KEY", "app"),
+        ]);
+        let status = |secrets: &HashMap<&str, &str>| {
+            let lookup = |var: &str| secrets.get(var).map(|value| value.to_string());
+            (provider_status(&lookup), secret_field_status(&lookup))
+        };
+        assert_eq!(status(&secrets).0["aliyun"], true);
+
+        secrets.insert("ALIYUN_APP_KEY", "  ");
+        let (providers, fields) = status(&secrets);
+        assert_eq!(providers["aliyun"], false);
+        assert_eq!(fields["aliyun"]["access_key_id"], true);
+        assert_eq!(fields["aliyun"]["app_key"], false);
+
+        secrets.remove("ALIYUN_APP_KEY");
+        assert_eq!(status(&secrets).0["aliyun"], false);
+
+        let response = invoke(
+            &runtime,
+            "delete_provider_secret",
+            json!({ "provider": "aliyun", "field": "access_key_id" }),
+        );
+        assert_eq!(response["ok"], false);
+        assert_eq!(response["error"]["code"], "unsupported");
+    }
+
+    fn save_profile(runtime: &Runtime, name: &str, domain: &str) -> String {
+        let response = invoke(
+            runtime,
+            "save_meeting_profile",
+            json!({
+                "name": name,
+                "meetingType": "interview",
+                "domain": domain,
+                "language": "zh-CN",
+                "selfIntro": "",
+                "contextNotes": ""
+            }),
+        );
+        response["data"]["id"]
+            .as_str()
+            .expect("profile id")
+            .to_string()
+    }
+
+    #[test]
+    fn attachments_are_added_and_deleted_per_profile() {
+        let (runtime, data_dir) = runtime();
+        let profile_id = save_profile(&runtime, "Backend interview", "rust");
+
+        let missing = invoke(
+            &runtime,
+            "extract_attachment_text",
+            json!({ "profileId": "missing", "filePath": "/tmp/cv.pdf" }),
+        );
+        assert_eq!(missing["error"]["code"], "profile_not_found");
+
+        let added = invoke(
+            &runtime,
+            "extract_attachment_text",
+            json!({ "profileId": profile_id, "filePath": "/tmp/cv.pdf", "fileType": "pdf" }),
+        );
+        let attachment_id = added["data"]["id"].as_str().expect("attachment id");
+        let stored = fs::read_to_string(data_dir.0.join(STATE_FILE_NAME)).expect("state file");
+        assert!(stored.contains(attachment_id));
+
+        let deleted = invoke(
+            &runtime,
+            "delete_attachment",
+            json!({ "profileId": profile_id, "attachmentId": attachment_id }),
+        );
+        assert_eq!(deleted["ok"], true);
+        let profiles = invoke(&runtime, "list_meeting_profiles", Value::Null);
+        assert_eq!(profiles["data"][0]["attachments"], json!([]));
+
+        let again = invoke(
+            &runtime,
+            "delete_attachment",
+            json!({ "profileId": profile_id, "attachmentId": attachment_id }),
+        );
+        assert_eq!(again["error"]["code"], "attachment_not_found");
+    }
+
+    #[test]
+    fn profiles_round_trip_through_export_and_import() {
+        let (runtime, _data_dir) = runtime();
+        let profile_id = save_profile(&runtime, "Backend interview", "rust");
+        save_profile(&runtime, "产品评审", "支付");
+        invoke(
+            &runtime,
+            "extract_attachment_text",
+            json!({ "profileId": profile_id, "filePath": "/tmp/cv.pdf" }),
+        );
+        let before = invoke(&runtime, "list_meeting_profiles", Value::Null)["data"].clone();
+        let bundle = invoke(&runtime, "export_profiles", Value::Null)["data"].clone();
+
+        for profile in before.as_array().expect("profiles") {
+            invoke(
+                &runtime,
+                "delete_meeting_profile",
+                json!({ "id": profile["id"] }),
+            );
+        }
+        assert_eq!(
+            invoke(&runtime, "list_meeting_profiles", Value::Null)["data"],
+            json!([])
+        );
+
+        let imported = invoke(&runtime, "import_profiles", json!({ "bundle": bundle }));
+        assert_eq!(imported["data"]["imported"], 2);
+        assert_eq!(
+            invoke(&runtime, "list_meeting_profiles", Value::Null)["data"],
+            before
+        );
+    }
+
+    #[test]
+    fn import_regenerates_ids_repeated_inside_a_bundle() {
+        let (runtime, _data_dir) = runtime();
+        let profile = MeetingProfile {
+            id: "dup".to_string(),
+            name: "Standup".to_string(),
+            ..MeetingProfile::default()
+        };
+        let bundle = ProfileBundle {
+            version: PROFILE_BUNDLE_VERSION,
+            exported_at: chrono_like_now(),
+            profiles: vec![profile.clone(), profile],
+        };
+        let bundle = serde_json::to_string(&bundle).expect("bundle serializes");
+
+        for merge in [false, true] {
+            invoke(
+                &runtime,
+                "import_profiles",
+                json!({ "bundle": bundle, "merge": merge }),
+            );
+            let profiles = invoke(&runtime, "list_meeting_profiles", Value::Null)["data"].clone();
+            let ids = profiles
+                .as_array()
+                .expect("profiles")
+                .iter()
+                .map(|profile| profile["id"].as_str().expect("id").to_string())
+                .collect::<HashSet<_>>();
+            assert_eq!(ids.len(), profiles.as_array().expect("profiles").len());
+        }
+    }
+
+    #[test]
+    fn sessions_are_listed_newest_first_and_deleted_once_stopped() {
+        let (runtime, _data_dir) = runtime();
+        let first = invoke(&runtime, "start_live_session", json!({ "profileId": "p1" }));
+        let first_id = first["data"]["sessionId"].clone();
+        let second = invoke(&runtime, "start_live_session", json!({ "restart": true }));
+        let second_id = second["data"]["sessionId"].clone();
+
+        let sessions = invoke(&runtime, "list_sessions", Value::Null)["data"].clone();
+        assert_eq!(sessions[0]["id"], second_id);
+        assert_eq!(sessions[0]["endedAt"], Value::Null);
+        assert_eq!(sessions[1]["id"], first_id);
+        assert_eq!(sessions[1]["profileId"], "p1");
+        assert!(sessions[1]["endedAt"].is_string());
+
+        let running = invoke(&runtime, "delete_session", json!({ "id": second_id }));
+        assert_eq!(running["error"]["code"], "session_running");
+
+        invoke(&runtime, "stop_live_session", Value::Null);
+        for id in [&first_id, &second_id] {
+            assert_eq!(
+                invoke(&runtime, "delete_session", json!({ "id": id }))["ok"],
+                true
+            );
+        }
+        assert_eq!(
+            invoke(&runtime, "list_sessions", Value::Null)["data"],
+            json!([])
+        );
+        let missing = invoke(&runtime, "delete_session", json!({ "id": first_id }));
+        assert_eq!(missing["error"]["code"], "session_not_found");
+    }
+
+    #[test]
+    fn search_profiles_matches_english_and_chinese_queries() {
+        let (runtime, _data_dir) = runtime();
+        save_profile(&runtime, "Backend interview", "RUST");
+        save_profile(&runtime, "Rust platform review", "infra");
+        save_profile(&runtime, "产品评审", "支付系统");
+
+        let search = |query: &str| {
+            invoke(&runtime, "search_profiles", json!({ "query": query }))["data"]
+                .as_array()
+                .expect("profiles")
+                .iter()
+                .map(|profile| profile["name"].as_str().expect("name").to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            search("rust"),
+            ["Rust platform review", "Backend interview"]
+        );
+        assert_eq!(search("  Backend "), ["Backend interview"]);
+        assert_eq!(search("评审"), ["产品评审"]);
+        assert_eq!(search("支付"), ["产品评审"]);
+        assert!(search("会议").is_empty());
+    }
+
+    #[test]
+    fn truncation_keeps_short_text_and_cuts_at_word_boundaries() {
+        assert_eq!(truncate_extracted_text("short text", 20), "short text");
+        assert_eq!(
+            truncate_extracted_text("hello world foo", 13),
+            "hello world\n[truncated]"
+        );
+    }
+
+    #[test]
+    fn truncation_counts_characters_in_multibyte_text() {
+        assert_eq!(
+            truncate_extracted_text("你好 世界 再见", 4),
+            "你好\n[truncated]"
+        );
+        assert_eq!(
+            truncate_extracted_text("会议助手测试", 3),
+            "会议助\n[truncated]"
+        );
+    }
+
+    #[test]
+    fn truncation_cuts_mid_word_without_whitespace() {
+        assert_eq!(truncate_extracted_text("abcdefgh", 4), "abcd\n[truncated]");
+        assert_eq!(truncate_extracted_text(" abcdefgh", 4), " abc\n[truncated]");
+    }
+
+    #[test]
+    fn truncation_keeps_the_last_word_when_the_cut_lands_on_a_boundary() {
+        assert_eq!(
+            truncate_extracted_text("hello world foo", 11),
+            "hello world\n[truncated]"
+        );
+        assert_eq!(
+            truncate_extracted_text("你好 世界 再见", 5),
+            "你好 世界\n[truncated]"
+        );
+    }
+
+    #[test]
+    fn event_callbacks_can_invoke_the_runtime() {
+        let (runtime, _data_dir) = runtime();
+        let runtime = Arc::new(runtime);
+        let reentrant = Arc::downgrade(&runtime);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_in_callback = Arc::clone(&seen);
+        runtime.set_event_callback(move |event, _| {
+            if let Some(runtime) = reentrant.upgrade() {
+                let response = runtime.invoke_json(r#"{"command":"get_user_preferences"}"#);
+                seen_in_callback
+                    .lock()
+                    .expect("seen mutex poisoned")
+                    .push((event.to_string(), response.contains(r#""ok":true"#)));
+            }
+        });
+
+        invoke(&runtime, "complete_onboarding", Value::Null);
+        invoke(
+            &runtime,
+            "set_show_interim",
+            json!({ "showInterim": false }),
+        );
+        invoke(&runtime, "set_translation_mode", json!({ "mode": "llm" }));
+        invoke(
+            &runtime,
+            "set_overlay_content_mode",
+            json!({ "mode": "both" }),
+        );
+        invoke(
+            &runtime,
+            "save_overlay_hotkey",
+            json!({ "hotkey": "CmdOrCtrl+Shift+H" }),
+        );
+
+        let seen = seen.lock().expect("seen mutex poisoned");
+        assert_eq!(seen.len(), 5);
+        assert!(seen.iter().all(|(_, ok)| *ok));
+    }
+
+    #[test]
+    fn llm_settings_saves_keep_fields_the_host_omits() {
+        let (runtime, _data_dir) = runtime();
+        let full = invoke(
+            &runtime,
+            "save_llm_settings",
+            json!({
+                "provider": "custom",
+                "model": "qwen",
+                "baseUrl": "https://gateway.example",
+                "apiFormat": "openai",
+                "systemPromptMode": "first_user_message",
+                "authStyle": "api_key_header",
+                "stopSequences": ["</answer>"],
+                "extraHeaders": { "X-Route": "eu" }
+            }),
+        );
+        assert_eq!(full["ok"], true);
+
+        let partial = invoke(
+            &runtime,
+            "save_llm_settings",
+            json!({
+                "provider": "custom",
+                "model": "qwen-max",
+                "baseUrl": "https://gateway.example",
+                "apiFormat": "openai"
+            }),
+        );
+        assert_eq!(partial["ok"], true);
+
+        let settings = invoke(&runtime, "get_llm_settings", Value::Null)["data"].clone();
+        assert_eq!(settings["model"], "qwen-max");
+        assert_eq!(settings["systemPromptMode"], "first_user_message");
+        assert_eq!(settings["authStyle"], "api_key_header");
+        assert_eq!(settings["stopSequences"], json!(["</answer>"]));
+        assert_eq!(settings["extraHeaders"], json!({ "x-route": "eu" }));
+
+        let cleared = invoke(
+            &runtime,
+            "save_llm_settings",
+            json!({ "stopSequences": [], "extraHeaders": {} }),
+        );
+        assert_eq!(cleared["data"]["stopSequences"], json!([]));
+        assert_eq!(cleared["data"]["extraHeaders"], json!({}));
+    }
+
+    fn runtime_in(data_dir: &TempDataDir) -> Runtime {
+        let config = json!({ "dataDir": data_dir.0, "platform": "linux" });
+        Runtime::new(&config.to_string()).expect("runtime starts")
+    }
+
+    #[test]
+    fn state_reloads_into_a_new_runtime() {
+        let (runtime, data_dir) = runtime();
+        save_profile(&runtime, "Backend interview", "rust");
+        invoke(&runtime, "set_translation_mode", json!({ "mode": "llm" }));
+        drop(runtime);
+
+        let reloaded = runtime_in(&data_dir);
+        let profiles = invoke(&reloaded, "list_meeting_profiles", Value::Null)["data"].clone();
+        assert_eq!(profiles[0]["name"], "Backend interview");
+        let preferences = invoke(&reloaded, "get_user_preferences", Value::Null)["data"].clone();
+        assert_eq!(preferences["translationMode"], "llm");
+        let stored = fs::read_to_string(data_dir.0.join(STATE_FILE_NAME)).expect("state file");
+        let stored = serde_json::from_str::<Value>(&stored).expect("state file is JSON");
+        assert_eq!(stored["schemaVersion"], STATE_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn read_only_commands_do_not_write_state() {
+        let (runtime, data_dir) = runtime();
+        save_profile(&runtime, "Backend interview", "rust");
+        let state_file = data_dir.0.join(STATE_FILE_NAME);
+        fs::remove_file(&state_file).expect("state file was written");
+
+        invoke(&runtime, "list_meeting_profiles", Value::Null);
+        invoke(&runtime, "get_bootstrap_state", Value::Null);
+        assert!(!state_file.exists());
+
+        invoke(
+            &runtime,
+            "set_show_interim",
+            json!({ "showInterim": false }),
+        );
+        assert!(state_file.exists());
+    }
+
+    #[test]
+    fn corrupt_state_is_moved_aside_without_replacing_earlier_backups() {
+        let (runtime, data_dir) = runtime();
+        drop(runtime);
+        fs::create_dir_all(&data_dir.0).expect("data dir");
+        let state_file = data_dir.0.join(STATE_FILE_NAME);
+
+        let mut backups = Vec::new();
+        for garbage in ["{not json", "[1, 2"] {
+            fs::write(&state_file, garbage).expect("write garbage");
+            let runtime = runtime_in(&data_dir);
+            let info = invoke(&runtime, "get_runtime_info", Value::Null)["data"].clone();
+            let backup = PathBuf::from(info["corruptStateBackup"].as_str().expect("backup path"));
+            assert_eq!(fs::read_to_string(&backup).expect("backup kept"), garbage);
+            assert!(!state_file.exists());
+            assert_eq!(
+                invoke(&runtime, "list_meeting_profiles", Value::Null)["data"],
+                json!([])
+            );
+            backups.push(backup);
+        }
+        assert_ne!(backups[0], backups[1]);
+        assert!(backups[0].exists());
+    }
+
+    #[test]
+    fn unreadable_state_fails_startup_instead_of_being_discarded() {
+        let (runtime, data_dir) = runtime();
+        drop(runtime);
+        // A directory in place of the file makes the read fail with an I/O error.
+        fs::create_dir_all(data_dir.0.join(STATE_FILE_NAME)).expect("state path");
+        let config = json!({ "dataDir": data_dir.0 });
+        assert!(Runtime::new(&config.to_string()).is_err());
+        assert!(data_dir.0.join(STATE_FILE_NAME).is_dir());
+    }
+
+    #[test]
+    fn profile_updates_keep_the_hint_prompt_unless_it_is_cleared() {
+        let (runtime, _data_dir) = runtime();
+        let profile = json!({
+            "name": "Backend interview",
+            "meetingType": "interview",
+            "domain": "rust",
+            "language": "en-US",
+            "selfIntro": "",
+            "contextNotes": ""
+        });
+        let mut with_prompt = profile.clone();
+        with_prompt["hintSystemPrompt"] = json!("Answer in two sentences.");
+        let id = invoke(&runtime, "save_meeting_profile", with_prompt)["data"]["id"].clone();
+
+        let mut omitted = profile.clone();
+        omitted["id"] = id.clone();
+        omitted["domain"] = json!("rust services");
+        let saved = invoke(&runtime, "save_meeting_profile", omitted);
+        assert_eq!(saved["data"]["domain"], "rust services");
+        assert_eq!(
+            saved["data"]["hintSystemPrompt"],
+            "Answer in two sentences."
+        );
+
+        let mut cleared = profile;
+        cleared["id"] = id;
+        cleared["hintSystemPrompt"] = json!("");
+        let saved = invoke(&runtime, "save_meeting_profile", cleared);
+        assert_eq!(saved["data"]["hintSystemPrompt"], Value::Null);
+    }
+
+    #[test]
+    fn sessions_left_open_by_a_previous_process_reload_as_interrupted() {
+        let (runtime, data_dir) = runtime();
+        let started = invoke(&runtime, "start_live_session", Value::Null);
+        drop(runtime);
+
+        let reloaded = runtime_in(&data_dir);
+        let sessions = invoke(&reloaded, "list_sessions", Value::Null)["data"].clone();
+        assert_eq!(sessions[0]["id"], started["data"]["sessionId"]);
+        assert!(sessions[0]["endedAt"].is_string());
+        assert_eq!(sessions[0]["interrupted"], true);
+        assert_eq!(
+            invoke(&reloaded, "session_status", Value::Null)["data"]["running"],
+            false
+        );
+    }
+
+    #[test]
+    fn extra_header_values_reject_control_characters() {
+        let (runtime, _data_dir) = runtime();
+        for value in ["a\r\nb", "a\0b", "a\u{7f}b"] {
+            let response = invoke(
+                &runtime,
+                "save_llm_settings",
+                json!({ "extraHeaders": { "x-route": value } }),
+            );
+            assert_eq!(response["error"]["code"], "invalid_payload");
+        }
+        let response = invoke(
+            &runtime,
+            "save_llm_settings",
+            json!({ "extraHeaders": { "x-route": "eu\twest" } }),
+        );
+        assert_eq!(response["ok"], true);
+    }
+
+    #[test]
+    fn snapping_handles_work_areas_near_the_i32_limit() {
+        let mut layout = LiveOverlayLayout::default();
+        let area = OverlayRegion {
+            x: i32::MAX - 100,
+            y: i32::MAX - 100,
+            width: u32::MAX,
+            height: u32::MAX,
+        };
+        assert!(layout.snap(&area, "bottom_right", 24));
+        assert_eq!((layout.x, layout.y), (i32::MAX, i32::MAX));
+
+        assert!(layout.snap(&area, "top_left", 24));
+        assert_eq!((layout.x, layout.y), (i32::MAX - 100, i32::MAX - 100));
+    }
+}
@@ -7,13 +7,23 @@
 extern "C" {
 #endif
 
-typedef void (*ma_event_callback)(const char* event_json, void* user_data);
+#define MEETING_CORE_FFI_ABI_VERSION 2
+
+// event_name is the routing key (e.g. "session://state"); event_json is the full
+// {"event", "payload"} envelope. Both are only valid for the duration of the call.
+typedef void (*ma_event_callback)(const char* event_name, const char* event_json, void* user_data);
 
 void* ma_runtime_new(const char* config_json);
 void ma_runtime_free(void* runtime_handle);
 char* ma_invoke_json(void* runtime_handle, const char* request_json);
 void ma_set_event_callback(void* runtime_handle, ma_event_callback callback, void* user_data);
 void ma_free_c_string(char* ptr);
+// Returns a static, NUL-terminated version string owned by the library. Unlike the
+// char* results above, it must not be passed to ma_free_c_string.
+const char* ma_runtime_version(void);
+// Returns the MEETING_CORE_FFI_ABI_VERSION the library was built with, so a host can
+// reject a shared library whose callback signature does not match its headers.
+uint32_t ma_ffi_abi_version(void);
 
 #ifdef __cplusplus
 }
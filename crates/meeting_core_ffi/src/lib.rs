@@ -1,13 +1,20 @@
 use std::{
     ffi::{c_char, c_void, CStr, CString},
     ptr,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, OnceLock},
 };
 
 use meeting_core::Runtime;
 use serde_json::json;
 
-type MaEventCallback = unsafe extern "C" fn(event_json: *const c_char, user_data: *mut c_void);
+// Mirrors MEETING_CORE_FFI_ABI_VERSION in meeting_core_ffi.h; bump both on ABI changes.
+const FFI_ABI_VERSION: u32 = 2;
+
+type MaEventCallback = unsafe extern "C" fn(
+    event_name: *const c_char,
+    event_json: *const c_char,
+    user_data: *mut c_void,
+);
 
 #[derive(Clone, Copy)]
 struct CallbackRegistration {
@@ -21,6 +28,10 @@ pub struct MaRuntimeHandle {
     callback: Arc<Mutex<Option<CallbackRegistration>>>,
 }
 
+/// # Safety
+///
+/// `config_json` must be null or point to a NUL-terminated string that stays valid for
+/// the duration of the call. The returned handle must be released with `ma_runtime_free`.
 #[no_mangle]
 pub unsafe extern "C" fn ma_runtime_new(config_json: *const c_char) -> *mut MaRuntimeHandle {
     let config = if config_json.is_null() {
@@ -45,7 +56,7 @@ pub unsafe extern "C" fn ma_runtime_new(config_json: *const c_char) -> *mut MaRu
 
     let callback = Arc::new(Mutex::new(None::<CallbackRegistration>));
     let callback_ref = Arc::clone(&callback);
-    runtime.set_event_callback(move |_event, payload| {
+    runtime.set_event_callback(move |event, payload| {
         let registration = {
             let guard = callback_ref
                 .lock()
@@ -54,9 +65,14 @@ pub unsafe extern "C" fn ma_runtime_new(config_json: *const c_char) -> *mut MaRu
         };
         if let Some(registration) = registration {
             let payload_json = payload.to_string();
-            if let Ok(c_payload) = CString::new(payload_json) {
+            if let (Ok(c_event), Ok(c_payload)) = (CString::new(event), CString::new(payload_json))
+            {
                 unsafe {
-                    (registration.callback)(c_payload.as_ptr(), registration.user_data as *mut c_void)
+                    (registration.callback)(
+                        c_event.as_ptr(),
+                        c_payload.as_ptr(),
+                        registration.user_data as *mut c_void,
+                    )
                 };
             }
         }
@@ -65,6 +81,10 @@ pub unsafe extern "C" fn ma_runtime_new(config_json: *const c_char) -> *mut MaRu
     Box::into_raw(Box::new(MaRuntimeHandle { runtime, callback }))
 }
 
+/// # Safety
+///
+/// `handle` must be null or a handle returned by `ma_runtime_new` that has not been freed.
+/// No other call may use the handle concurrently or afterwards.
 #[no_mangle]
 pub unsafe extern "C" fn ma_runtime_free(handle: *mut MaRuntimeHandle) {
     if handle.is_null() {
@@ -79,6 +99,11 @@ pub unsafe extern "C" fn ma_runtime_free(handle: *mut MaRuntimeHandle) {
     *guard = None;
 }
 
+/// # Safety
+///
+/// `handle` must be null or a live handle from `ma_runtime_new`. `callback` may be invoked
+/// from any thread with `user_data`, which must stay valid until the callback is replaced,
+/// cleared, or the handle is freed.
 #[no_mangle]
 pub unsafe extern "C" fn ma_set_event_callback(
     handle: *mut MaRuntimeHandle,
@@ -100,6 +125,11 @@ pub unsafe extern "C" fn ma_set_event_callback(
     });
 }
 
+/// # Safety
+///
+/// `handle` must be null or a live handle from `ma_runtime_new`, and `request_json` must be
+/// null or a NUL-terminated string. The returned string must be released with
+/// `ma_free_c_string`.
 #[no_mangle]
 pub unsafe extern "C" fn ma_invoke_json(
     handle: *mut MaRuntimeHandle,
@@ -127,6 +157,22 @@ pub unsafe extern "C" fn ma_invoke_json(
     into_c_string(response)
 }
 
+#[no_mangle]
+pub extern "C" fn ma_runtime_version() -> *const c_char {
+    static VERSION: OnceLock<CString> = OnceLock::new();
+    VERSION
+        .get_or_init(|| CString::new(Runtime::version()).expect("version has no NUL bytes"))
+        .as_ptr()
+}
+
+#[no_mangle]
+pub extern "C" fn ma_ffi_abi_version() -> u32 {
+    FFI_ABI_VERSION
+}
+
+/// # Safety
+///
+/// `ptr` must be null or a string returned by `ma_invoke_json` that has not been freed.
 #[no_mangle]
 pub unsafe extern "C" fn ma_free_c_string(ptr: *mut c_char) {
     if ptr.is_null() {